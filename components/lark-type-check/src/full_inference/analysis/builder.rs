@@ -9,7 +9,10 @@ use crate::full_inference::Perm;
 use crate::results::TypeCheckResults;
 use lark_collections::map::Entry;
 use lark_collections::FxIndexMap;
+use lark_entity::Entity;
+use lark_error::Diagnostic;
 use lark_hir as hir;
+use lark_string::global::GlobalIdentifier;
 use lark_ty as ty;
 use lark_unify::UnificationTable;
 
@@ -26,6 +29,14 @@ impl AnalysisBuilder<'_> {
         self.analysis.node_datas.push(data)
     }
 
+    /// Re-tags an already-pushed node, e.g. when an always-diverging
+    /// child turns out to be the only reachable node for some
+    /// enclosing expression too, and that expression needs to be the
+    /// one a later lookup by `Node` finds there.
+    fn retag_node(&mut self, node: Node, data: NodeData) {
+        self.analysis.node_datas[node] = data;
+    }
+
     fn push_node_edge(&mut self, start_node: Node, data: NodeData) -> Node {
         let n = self.push_node(data);
         self.push_edge(start_node, n);
@@ -37,27 +48,94 @@ impl AnalysisBuilder<'_> {
     }
 
     fn node(&mut self, start_node: Node, n: impl IntoNode) -> Node {
+        self.node_diverges(start_node, n).0
+    }
+
+    /// Like `node`, but also reports whether control flow can ever
+    /// reach past `n` (see `Diverges`).
+    fn node_diverges(&mut self, start_node: Node, n: impl IntoNode) -> (Node, Diverges) {
         n.to_cfg_node(start_node, self)
     }
 
-    /// Converts a HIR "Place" into an analysis *path*
-    fn path(&mut self, place: hir::Place) -> Path {
+    /// A `Call`/`MethodCall` diverges if it's known to never return,
+    /// i.e. its result type is the bottom/never type.
+    fn call_diverges(&self, expression: hir::Expression) -> Diverges {
+        if self.results.is_never_type(self.results.ty(expression)) {
+            Diverges::Always
+        } else {
+            Diverges::Maybe
+        }
+    }
+
+    /// Converts a HIR "Place" into an analysis *path*. `node` is the
+    /// CFG node that the resulting access facts (if any, e.g. from
+    /// autoderef) should be attached to.
+    fn path(&mut self, node: Node, place: hir::Place) -> Path {
         match self.fn_body[place] {
             hir::PlaceData::Variable(v) => self.create_path(PathData::Variable(v)),
             hir::PlaceData::Entity(e) => self.create_path(PathData::Entity(e)),
             hir::PlaceData::Temporary(e) => self.create_path(PathData::Temporary(e)),
             hir::PlaceData::Field { owner, name } => {
                 let name = self.fn_body[name].text;
-                let owner = self.path(owner);
-                if false {
-                    // dummy code to stop errors
-                    self.create_path(PathData::Index { owner });
-                }
+                let owner = self.autoderef_path(node, owner);
                 self.create_path(PathData::Field { owner, name })
             }
+            hir::PlaceData::Deref { owner } => {
+                let owner = self.path(node, owner);
+                self.create_path(PathData::Deref { owner })
+            }
+            hir::PlaceData::Index { owner, .. } => {
+                // `PathData::Index` is imprecise (see its `precise()`
+                // impl): we don't track *which* element was written,
+                // so `a[i] = x` must not mark all of `a` as
+                // overwritten, only as traversed. The index operand
+                // itself is already recorded as used by `IntoNode for
+                // hir::Place`'s `Index` arm while building the CFG.
+                let owner = self.autoderef_path(node, owner);
+                self.create_path(PathData::Index { owner })
+            }
         }
     }
 
+    /// Returns the type of `place`, i.e. the type of the owning
+    /// variable/temporary/field, resolved as far as inference
+    /// currently allows.
+    fn place_ty(&self, place: hir::Place) -> ty::Ty<FullInference> {
+        self.results.ty(place)
+    }
+
+    /// Repeatedly sees through smart-pointer/`Deref` layers on
+    /// `place`'s type, inserting one `PathData::Deref` path and one
+    /// access fact (the deref reading the pointer's own permission
+    /// before the pointee is reached) per layer. Bails out after
+    /// `MAX_AUTODEREF_STEPS` layers, or as soon as the base type is an
+    /// unresolved inference variable, so a recursive `Deref` impl
+    /// can't make this loop forever.
+    fn autoderef_path(&mut self, node: Node, place: hir::Place) -> Path {
+        const MAX_AUTODEREF_STEPS: usize = 8;
+
+        let mut path = self.path(node, place);
+        let mut ty = self.place_ty(place);
+
+        for _ in 0..MAX_AUTODEREF_STEPS {
+            let base = match self.unify.shallow_resolve_data(ty.base) {
+                Ok(base_data) => base_data,
+                Err(_) => break,
+            };
+
+            let pointee = match self.results.deref_target(base) {
+                Some(pointee) => pointee,
+                None => break,
+            };
+
+            self.access(ty.perm, path, node);
+            path = self.create_path(PathData::Deref { owner: path });
+            ty = pointee;
+        }
+
+        path
+    }
+
     fn create_path(&mut self, path_data: PathData) -> Path {
         match self.path_datas.entry(path_data) {
             Entry::Occupied(entry) => Path::from(entry.index()),
@@ -91,6 +169,56 @@ impl AnalysisBuilder<'_> {
         }
     }
 
+    /// Checks that an aggregate expression (`Foo { a, b }`) provides
+    /// exactly the fields declared on `entity`. A declared field that
+    /// wasn't provided is reported as a missing-fields diagnostic;
+    /// a provided field that isn't declared on `entity` at all is
+    /// reported separately, since the two call for different fixes
+    /// (add the missing field vs. remove the bogus one).
+    fn check_aggregate_fields(
+        &mut self,
+        expression: hir::Expression,
+        entity: Entity,
+        fields: hir::List<hir::IdentifiedExpression>,
+    ) {
+        let declared = self.results.declared_fields(entity);
+
+        let provided: Vec<GlobalIdentifier> = fields
+            .iter(self.fn_body)
+            .map(|field| self.fn_body[self.fn_body[field].identifier].text)
+            .collect();
+
+        let (missing, _extra) = missing_and_extra_fields(&declared, &provided);
+
+        if !missing.is_empty() {
+            let mut message = String::from("Missing structure fields:");
+            for name in &missing {
+                // `name` is just an interned id; resolve it back to
+                // its text before it goes anywhere near a diagnostic.
+                message.push_str(&format!("\n- {}", self.results.identifier_text(*name)));
+            }
+            self.report_diagnostic(message, expression);
+        }
+
+        // Re-walk `fields` (rather than relying on `_extra`, which
+        // only has the interned names) so each "no such field"
+        // diagnostic can point at that one field's own expression
+        // instead of the whole aggregate.
+        for field in fields.iter(self.fn_body) {
+            let name = self.fn_body[self.fn_body[field].identifier].text;
+            if !declared.contains(&name) {
+                let text = self.results.identifier_text(name);
+                let field_expression = self.fn_body[field].expression;
+                self.report_diagnostic(format!("no such field `{}`", text), field_expression);
+            }
+        }
+    }
+
+    fn report_diagnostic(&mut self, message: String, expression: hir::Expression) {
+        let span = self.fn_body.span(expression);
+        self.analysis.diagnostics.push(Diagnostic::new(message, span));
+    }
+
     fn use_result_of(&mut self, node: Node, expression: hir::Expression) {
         let expression_ty = self.results.ty(expression);
         self.use_ty(node, expression_ty);
@@ -126,18 +254,107 @@ impl AnalysisBuilder<'_> {
     }
 }
 
+/// Splits `declared` and `provided` field names into the ones that are
+/// declared but missing from `provided`, and the ones that are
+/// provided but not in `declared`. Generic (rather than hardcoded to
+/// `GlobalIdentifier`) so the set logic can be unit-tested without an
+/// interner to hand.
+fn missing_and_extra_fields<T: Clone + PartialEq>(
+    declared: &[T],
+    provided: &[T],
+) -> (Vec<T>, Vec<T>) {
+    let missing = declared
+        .iter()
+        .cloned()
+        .filter(|name| !provided.contains(name))
+        .collect();
+    let extra = provided
+        .iter()
+        .cloned()
+        .filter(|name| !declared.contains(name))
+        .collect();
+    (missing, extra)
+}
+
+#[cfg(test)]
+mod missing_and_extra_fields_tests {
+    use super::missing_and_extra_fields;
+
+    #[test]
+    fn all_fields_provided() {
+        let (missing, extra) = missing_and_extra_fields(&["a", "b"], &["a", "b"]);
+        assert!(missing.is_empty());
+        assert!(extra.is_empty());
+    }
+
+    #[test]
+    fn missing_field_is_reported() {
+        let (missing, extra) = missing_and_extra_fields(&["a", "b"], &["a"]);
+        assert_eq!(missing, vec!["b"]);
+        assert!(extra.is_empty());
+    }
+
+    #[test]
+    fn unknown_field_is_reported() {
+        let (missing, extra) = missing_and_extra_fields(&["a"], &["a", "b"]);
+        assert!(missing.is_empty());
+        assert_eq!(extra, vec!["b"]);
+    }
+
+    #[test]
+    fn missing_and_unknown_fields_are_independent() {
+        let (missing, extra) = missing_and_extra_fields(&["a", "b"], &["a", "c"]);
+        assert_eq!(missing, vec!["b"]);
+        assert_eq!(extra, vec!["c"]);
+    }
+}
+
+/// Whether control flow can ever proceed past a given CFG node. A node
+/// that `Always` diverges (e.g. a call to a function returning the
+/// never type) has no reachable successors, so the invariant upheld
+/// throughout this module is: `cfg_edges` must never contain a
+/// forward edge out of a node whose `Diverges` is `Always`. Tracking
+/// this here, rather than leaving it implicit, is what lets the
+/// downstream `accesses`/`overwritten` dataflow stop at the diverging
+/// node instead of spuriously propagating liveness into code after it
+/// that can never run.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Diverges {
+    Maybe,
+    Always,
+}
+
+impl Diverges {
+    fn is_always(self) -> bool {
+        self == Diverges::Always
+    }
+}
+
 trait IntoNode: Copy {
-    fn to_cfg_node(self, start_node: Node, builder: &mut AnalysisBuilder<'_>) -> Node;
+    fn to_cfg_node(self, start_node: Node, builder: &mut AnalysisBuilder<'_>) -> (Node, Diverges);
 }
 
 impl IntoNode for hir::Expression {
-    fn to_cfg_node(self, start_node: Node, builder: &mut AnalysisBuilder<'_>) -> Node {
+    fn to_cfg_node(self, start_node: Node, builder: &mut AnalysisBuilder<'_>) -> (Node, Diverges) {
         match &builder.fn_body[self] {
             hir::ExpressionData::Let {
                 initializer, body, ..
             } => {
                 // First, we evaluate `I`...
-                let initializer_node = builder.node(start_node, initializer);
+                let (initializer_node, initializer_diverges) =
+                    builder.node_diverges(start_node, initializer);
+
+                if initializer_diverges.is_always() {
+                    // The binding, and `body`, can never run -- but we
+                    // still build `body`'s subgraph, rooted at a
+                    // detached node, so diagnostics inside it still
+                    // surface. We must not draw an edge out of
+                    // `initializer_node`, since it never returns.
+                    let detached_root = builder.push_node(NodeData::Unreachable);
+                    builder.node(detached_root, body);
+                    builder.retag_node(initializer_node, NodeData::Expression(self));
+                    return (initializer_node, Diverges::Always);
+                }
 
                 // Next, the result of that is assigned into the
                 // variable `X`. This occurs at the node associated with the `let` itself.
@@ -148,55 +365,104 @@ impl IntoNode for hir::Expression {
                 }
 
                 // Finally, the body `B` is evaluated.
-                builder.node(self_node, body)
+                builder.node_diverges(self_node, body)
             }
 
             hir::ExpressionData::Place { place, .. } => {
-                let place_node = builder.node(start_node, place);
+                let (place_node, place_diverges) = builder.node_diverges(start_node, place);
+
+                if place_diverges.is_always() {
+                    builder.retag_node(place_node, NodeData::Expression(self));
+                    return (place_node, Diverges::Always);
+                }
+
                 let self_node = builder.push_node_edge(place_node, NodeData::Expression(self));
 
                 let perm = builder.results.access_permissions[&self];
-                let path = builder.path(*place);
+                let path = builder.path(self_node, *place);
                 builder.access(perm, path, self_node);
 
-                self_node
+                (self_node, Diverges::Maybe)
             }
 
             hir::ExpressionData::Assignment { place, value } => {
-                let place_node = builder.node(start_node, place);
-                let value_node = builder.node(place_node, value);
+                let (place_node, place_diverges) = builder.node_diverges(start_node, place);
+
+                if place_diverges.is_always() {
+                    // `value` can never execute, but build its
+                    // subgraph anyway (from a detached root) so
+                    // diagnostics inside it still surface.
+                    let detached_root = builder.push_node(NodeData::Unreachable);
+                    builder.node(detached_root, value);
+                    builder.retag_node(place_node, NodeData::Expression(self));
+                    return (place_node, Diverges::Always);
+                }
+
+                let (value_node, value_diverges) = builder.node_diverges(place_node, value);
+
+                if value_diverges.is_always() {
+                    builder.retag_node(value_node, NodeData::Expression(self));
+                    return (value_node, Diverges::Always);
+                }
+
                 let self_node = builder.push_node_edge(value_node, NodeData::Expression(self));
 
-                let path = builder.path(*place);
+                let path = builder.path(self_node, *place);
                 builder.generate_assignment_facts(path, self_node);
 
-                self_node
+                (self_node, Diverges::Maybe)
             }
 
             hir::ExpressionData::MethodCall { arguments, .. } => {
-                let arguments_node = builder.node(start_node, arguments);
+                let (arguments_node, arguments_diverges) =
+                    builder.node_diverges(start_node, arguments);
+
+                if arguments_diverges.is_always() {
+                    builder.retag_node(arguments_node, NodeData::Expression(self));
+                    return (arguments_node, Diverges::Always);
+                }
+
                 let self_node = builder.push_node_edge(arguments_node, NodeData::Expression(self));
 
                 for argument in arguments.iter(builder.fn_body) {
                     builder.use_result_of(self_node, argument);
                 }
 
-                self_node
+                (self_node, builder.call_diverges(self))
             }
 
             hir::ExpressionData::Call {
                 function,
                 arguments,
             } => {
-                let function_node = builder.node(start_node, function);
-                let arguments_node = builder.node(function_node, arguments);
+                let (function_node, function_diverges) =
+                    builder.node_diverges(start_node, function);
+
+                if function_diverges.is_always() {
+                    // `arguments` can never execute, but build their
+                    // subgraphs anyway (from a detached root) so
+                    // diagnostics inside them still surface.
+                    let detached_root = builder.push_node(NodeData::Unreachable);
+                    builder.node(detached_root, arguments);
+                    builder.retag_node(function_node, NodeData::Expression(self));
+                    return (function_node, Diverges::Always);
+                }
+
+                let (arguments_node, arguments_diverges) =
+                    builder.node_diverges(function_node, arguments);
+
+                if arguments_diverges.is_always() {
+                    builder.retag_node(arguments_node, NodeData::Expression(self));
+                    return (arguments_node, Diverges::Always);
+                }
+
                 let self_node = builder.push_node_edge(arguments_node, NodeData::Expression(self));
 
                 for argument in arguments.iter(builder.fn_body) {
                     builder.use_result_of(self_node, argument);
                 }
 
-                self_node
+                (self_node, builder.call_diverges(self))
             }
 
             hir::ExpressionData::If {
@@ -211,98 +477,486 @@ impl IntoNode for hir::Expression {
                 builder.use_result_of(self_node, *condition);
 
                 // Then the arms come afterwards:
-                let if_true_node = builder.node(self_node, if_true);
-                let if_false_node = builder.node(self_node, if_false);
+                let (if_true_node, if_true_diverges) = builder.node_diverges(self_node, if_true);
+                let (if_false_node, if_false_diverges) = builder.node_diverges(self_node, if_false);
 
-                // Create a node to rejoin the control-flows:
+                // Create a node to rejoin the control-flows. An arm
+                // that always diverges has no successor, so it must
+                // not be wired into the join.
                 let join_node = builder.push_node(NodeData::Join(self));
-                builder.push_edge(if_true_node, join_node);
-                builder.push_edge(if_false_node, join_node);
+                if !if_true_diverges.is_always() {
+                    builder.push_edge(if_true_node, join_node);
+                }
+                if !if_false_diverges.is_always() {
+                    builder.push_edge(if_false_node, join_node);
+                }
+
+                let diverges = if if_true_diverges.is_always() && if_false_diverges.is_always() {
+                    Diverges::Always
+                } else {
+                    Diverges::Maybe
+                };
 
-                join_node
+                (join_node, diverges)
             }
 
             hir::ExpressionData::Binary { left, right, .. } => {
-                let left_node = builder.node(start_node, left);
-                let right_node = builder.node(left_node, right);
+                let (left_node, left_diverges) = builder.node_diverges(start_node, left);
+
+                if left_diverges.is_always() {
+                    // `right` can never execute, but build its
+                    // subgraph anyway (from a detached root) so
+                    // diagnostics inside it still surface.
+                    let detached_root = builder.push_node(NodeData::Unreachable);
+                    builder.node(detached_root, right);
+                    builder.retag_node(left_node, NodeData::Expression(self));
+                    return (left_node, Diverges::Always);
+                }
+
+                let (right_node, right_diverges) = builder.node_diverges(left_node, right);
+
+                if right_diverges.is_always() {
+                    builder.retag_node(right_node, NodeData::Expression(self));
+                    return (right_node, Diverges::Always);
+                }
+
                 let self_node = builder.push_node_edge(right_node, NodeData::Expression(self));
                 builder.use_result_of(self_node, *left);
                 builder.use_result_of(self_node, *right);
-                self_node
+                (self_node, Diverges::Maybe)
             }
 
             hir::ExpressionData::Unary { value, .. } => {
-                let value_node = builder.node(start_node, value);
+                let (value_node, value_diverges) = builder.node_diverges(start_node, value);
+
+                if value_diverges.is_always() {
+                    builder.retag_node(value_node, NodeData::Expression(self));
+                    return (value_node, Diverges::Always);
+                }
+
                 let self_node = builder.push_node_edge(value_node, NodeData::Expression(self));
                 builder.use_result_of(self_node, *value);
-                self_node
+                (self_node, Diverges::Maybe)
             }
 
             hir::ExpressionData::Error { .. }
             | hir::ExpressionData::Unit {}
-            | hir::ExpressionData::Literal { .. } => {
-                builder.push_node_edge(start_node, NodeData::Expression(self))
-            }
+            | hir::ExpressionData::Literal { .. } => (
+                builder.push_node_edge(start_node, NodeData::Expression(self)),
+                Diverges::Maybe,
+            ),
+
+            hir::ExpressionData::Aggregate { entity, fields } => {
+                let (field_node, field_diverges) = builder.node_diverges(start_node, fields);
+
+                // Field-shape checking doesn't depend on reachability,
+                // so run it regardless of whether `fields` diverges.
+                builder.check_aggregate_fields(self, *entity, *fields);
+
+                if field_diverges.is_always() {
+                    builder.retag_node(field_node, NodeData::Expression(self));
+                    return (field_node, Diverges::Always);
+                }
 
-            hir::ExpressionData::Aggregate { fields, .. } => {
-                let field_node = builder.node(start_node, fields);
                 let self_node = builder.push_node_edge(field_node, NodeData::Expression(self));
                 for field in fields.iter(builder.fn_body) {
                     builder.use_result_of(self_node, builder.fn_body[field].expression);
                 }
-                self_node
+                (self_node, Diverges::Maybe)
             }
 
             hir::ExpressionData::Sequence { first, second } => {
-                let first_node = builder.node(start_node, first);
-                let second_node = builder.node(first_node, second);
-                builder.push_node_edge(second_node, NodeData::Expression(self))
+                let (first_node, first_diverges) = builder.node_diverges(start_node, first);
+
+                if first_diverges.is_always() {
+                    // `second` can never execute. We still build its
+                    // subgraph, rooted at a detached node, so that
+                    // diagnostics inside it are still produced -- but
+                    // we must not draw an edge out of `first_node`,
+                    // since it's the node that never returns.
+                    let unreachable_root = builder.push_node(NodeData::Expression(*second));
+                    builder.node(unreachable_root, second);
+
+                    // `first_node` is the only live node for this whole
+                    // `Sequence`, so it has to stand in for `self` too --
+                    // re-tag it so that a later lookup from `self`
+                    // resolves to it (the unreachable root above is
+                    // tagged with `second`, not `self`).
+                    builder.retag_node(first_node, NodeData::Expression(self));
+                    return (first_node, Diverges::Always);
+                }
+
+                let (second_node, second_diverges) = builder.node_diverges(first_node, second);
+
+                if second_diverges.is_always() {
+                    builder.retag_node(second_node, NodeData::Expression(self));
+                    return (second_node, Diverges::Always);
+                }
+
+                let self_node = builder.push_node_edge(second_node, NodeData::Expression(self));
+                (self_node, Diverges::Maybe)
             }
         }
     }
 }
 
 impl IntoNode for hir::IdentifiedExpression {
-    fn to_cfg_node(self, start_node: Node, builder: &mut AnalysisBuilder<'_>) -> Node {
-        builder.node(start_node, builder.fn_body[self].expression)
+    fn to_cfg_node(self, start_node: Node, builder: &mut AnalysisBuilder<'_>) -> (Node, Diverges) {
+        builder.node_diverges(start_node, builder.fn_body[self].expression)
     }
 }
 
 impl IntoNode for hir::Place {
-    fn to_cfg_node(self, start_node: Node, builder: &mut AnalysisBuilder<'_>) -> Node {
+    fn to_cfg_node(self, start_node: Node, builder: &mut AnalysisBuilder<'_>) -> (Node, Diverges) {
         match &builder.fn_body[self] {
-            hir::PlaceData::Variable(_) => start_node,
+            hir::PlaceData::Variable(_) => (start_node, Diverges::Maybe),
+
+            hir::PlaceData::Entity(_) => (start_node, Diverges::Maybe),
 
-            hir::PlaceData::Entity(_) => start_node,
+            // A place can diverge too, e.g. `(panic_fn()).field` --
+            // propagate whatever the wrapped expression/owner reports
+            // rather than hardcoding `Maybe`.
+            hir::PlaceData::Temporary(expression) => builder.node_diverges(start_node, expression),
 
-            hir::PlaceData::Temporary(expression) => builder.node(start_node, expression),
+            hir::PlaceData::Field { owner, .. } => builder.node_diverges(start_node, owner),
 
-            hir::PlaceData::Field { owner, .. } => builder.node(start_node, owner),
+            hir::PlaceData::Deref { owner } => {
+                // The pointer value itself is read (its permission is
+                // accessed) before the pointee can be reached.
+                let (owner_node, owner_diverges) = builder.node_diverges(start_node, owner);
+                let owner_path = builder.path(owner_node, *owner);
+                let owner_ty = builder.place_ty(*owner);
+                builder.access(owner_ty.perm, owner_path, owner_node);
+                (owner_node, owner_diverges)
+            }
+
+            hir::PlaceData::Index { owner, index } => {
+                let (owner_node, owner_diverges) = builder.node_diverges(start_node, owner);
+
+                if owner_diverges.is_always() {
+                    // `index` can never execute, but build its
+                    // subgraph anyway (from a detached root) so
+                    // diagnostics inside it still surface.
+                    let detached_root = builder.push_node(NodeData::Unreachable);
+                    let index_node = builder.node(detached_root, index);
+                    builder.use_result_of(index_node, *index);
+                    return (owner_node, Diverges::Always);
+                }
+
+                let (index_node, index_diverges) = builder.node_diverges(owner_node, index);
+                builder.use_result_of(index_node, *index);
+                (index_node, index_diverges)
+            }
         }
     }
 }
 
 impl<N: IntoNode + hir::HirIndex> IntoNode for hir::List<N> {
-    fn to_cfg_node(self, start_node: Node, builder: &mut AnalysisBuilder<'_>) -> Node {
+    fn to_cfg_node(self, start_node: Node, builder: &mut AnalysisBuilder<'_>) -> (Node, Diverges) {
         let mut n = start_node;
+        let mut diverges = Diverges::Maybe;
+
         for elem in self.iter(builder.fn_body) {
-            n = builder.node(n, elem);
+            if diverges.is_always() {
+                // An earlier element already diverged, so `elem` (and
+                // anything after it) can never execute -- but we
+                // still build its subgraph, rooted at its own
+                // detached node, so that diagnostics inside it (e.g.
+                // a missing struct field several arguments after a
+                // `panic!()`) are still produced. We must not chain
+                // it off of `n`, which never returns.
+                let detached_root = builder.push_node(NodeData::Unreachable);
+                builder.node(detached_root, elem);
+                continue;
+            }
+
+            let (next_n, elem_diverges) = builder.node_diverges(n, elem);
+            n = next_n;
+            if elem_diverges.is_always() {
+                diverges = Diverges::Always;
+            }
         }
-        n
+
+        (n, diverges)
     }
 }
 
 impl<N: IntoNode> IntoNode for Option<N> {
-    fn to_cfg_node(self, start_node: Node, builder: &mut AnalysisBuilder<'_>) -> Node {
+    fn to_cfg_node(self, start_node: Node, builder: &mut AnalysisBuilder<'_>) -> (Node, Diverges) {
         match self {
-            None => start_node,
-            Some(v) => builder.node(start_node, v),
+            None => (start_node, Diverges::Maybe),
+            Some(v) => builder.node_diverges(start_node, v),
         }
     }
 }
 
 impl<N: IntoNode + Copy> IntoNode for &N {
-    fn to_cfg_node(self, start_node: Node, builder: &mut AnalysisBuilder<'_>) -> Node {
+    fn to_cfg_node(self, start_node: Node, builder: &mut AnalysisBuilder<'_>) -> (Node, Diverges) {
         N::to_cfg_node(*self, start_node, builder)
     }
 }
+
+/// The seed of a `consteval` subsystem akin to hir-ty's: folds
+/// constant `Literal`/`Unary`/`Binary` expressions down to a
+/// `ConstValue`, memoized through salsa so repeated folds (e.g. the
+/// same expression referenced from several places) are incremental.
+/// Anything that isn't a closed-over integer or boolean constant
+/// yields `None`; this will grow to cover things like array lengths
+/// and compile-time conditions.
+mod consteval {
+    use lark_error::Diagnostic;
+    use lark_hir as hir;
+
+    #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+    pub enum ConstValue {
+        Int(i64),
+        Bool(bool),
+    }
+
+    /// The query's return value: the folded value, if any, plus any
+    /// diagnostics (e.g. overflow) produced while folding it. Bundled
+    /// into the memoized result itself, rather than pushed out
+    /// through a side-effecting call on `db` -- a call the query
+    /// makes once is still there to read back on every later cache
+    /// hit.
+    #[derive(Clone, Debug, PartialEq, Eq)]
+    pub struct ConstEvalResult {
+        pub value: Option<ConstValue>,
+        pub diagnostics: Vec<Diagnostic>,
+    }
+
+    salsa::query_group! {
+        pub trait ConstEvalDatabase: hir::HirDatabase {
+            fn const_eval(key: (hir::FnBody, hir::Expression)) -> ConstEvalResult {
+                type ConstEvalQuery;
+                use fn consteval::const_eval;
+            }
+        }
+    }
+
+    crate fn const_eval(
+        db: &impl ConstEvalDatabase,
+        key: (hir::FnBody, hir::Expression),
+    ) -> ConstEvalResult {
+        let (fn_body_id, expression) = key;
+        let fn_body = db.fn_body(fn_body_id);
+
+        match &fn_body[expression] {
+            hir::ExpressionData::Literal { value } => ConstEvalResult {
+                value: literal_to_const(value),
+                diagnostics: Vec::new(),
+            },
+
+            hir::ExpressionData::Unary { operator, value } => {
+                let mut operand = db.const_eval((fn_body_id, *value));
+                let value = operand.value.and_then(|value| {
+                    eval_unary(db, key, *operator, value, &mut operand.diagnostics)
+                });
+                ConstEvalResult {
+                    value,
+                    diagnostics: operand.diagnostics,
+                }
+            }
+
+            hir::ExpressionData::Binary {
+                operator,
+                left,
+                right,
+            } => {
+                let left = db.const_eval((fn_body_id, *left));
+                let right = db.const_eval((fn_body_id, *right));
+
+                let mut diagnostics = left.diagnostics;
+                diagnostics.extend(right.diagnostics);
+
+                let value = match (left.value, right.value) {
+                    (Some(left), Some(right)) => {
+                        eval_binary(db, key, *operator, left, right, &mut diagnostics)
+                    }
+                    _ => None,
+                };
+
+                ConstEvalResult { value, diagnostics }
+            }
+
+            _ => ConstEvalResult {
+                value: None,
+                diagnostics: Vec::new(),
+            },
+        }
+    }
+
+    fn literal_to_const(literal: &hir::LiteralData) -> Option<ConstValue> {
+        match literal.value {
+            hir::LiteralValue::Int(value) => Some(ConstValue::Int(value)),
+            hir::LiteralValue::Bool(value) => Some(ConstValue::Bool(value)),
+            _ => None,
+        }
+    }
+
+    fn eval_unary(
+        db: &impl ConstEvalDatabase,
+        key: (hir::FnBody, hir::Expression),
+        operator: hir::UnaryOperator,
+        value: ConstValue,
+        diagnostics: &mut Vec<Diagnostic>,
+    ) -> Option<ConstValue> {
+        match (operator, value) {
+            (hir::UnaryOperator::Not, ConstValue::Bool(value)) => Some(ConstValue::Bool(!value)),
+
+            (hir::UnaryOperator::Neg, ConstValue::Int(value)) => {
+                checked_int(value.checked_neg(), db, key, diagnostics)
+            }
+
+            _ => None,
+        }
+    }
+
+    /// The result of folding an integer binary op, before any
+    /// diagnostic gets attached to a span. Factored out of
+    /// `eval_binary` so the overflow/division-by-zero distinction can
+    /// be unit-tested without a database to hand.
+    #[derive(Copy, Clone, Debug, PartialEq, Eq)]
+    enum ArithOutcome {
+        Value(i64),
+        Overflow,
+        DivByZero,
+    }
+
+    fn eval_int_binary(
+        operator: hir::BinaryOperator,
+        left: i64,
+        right: i64,
+    ) -> Option<ArithOutcome> {
+        Some(match operator {
+            hir::BinaryOperator::Add => {
+                left.checked_add(right).map_or(ArithOutcome::Overflow, ArithOutcome::Value)
+            }
+            hir::BinaryOperator::Sub => {
+                left.checked_sub(right).map_or(ArithOutcome::Overflow, ArithOutcome::Value)
+            }
+            hir::BinaryOperator::Mul => {
+                left.checked_mul(right).map_or(ArithOutcome::Overflow, ArithOutcome::Value)
+            }
+
+            // Division by zero and overflow are both `None` from
+            // `checked_div`, but they're distinct conditions and
+            // deserve distinct diagnostics -- check for the former
+            // ourselves rather than mislabeling it as overflow.
+            hir::BinaryOperator::Div if right == 0 => ArithOutcome::DivByZero,
+            hir::BinaryOperator::Div => {
+                left.checked_div(right).map_or(ArithOutcome::Overflow, ArithOutcome::Value)
+            }
+
+            _ => return None,
+        })
+    }
+
+    fn eval_binary(
+        db: &impl ConstEvalDatabase,
+        key: (hir::FnBody, hir::Expression),
+        operator: hir::BinaryOperator,
+        left: ConstValue,
+        right: ConstValue,
+        diagnostics: &mut Vec<Diagnostic>,
+    ) -> Option<ConstValue> {
+        let (left, right) = match (left, right) {
+            (ConstValue::Int(left), ConstValue::Int(right)) => (left, right),
+            _ => return None,
+        };
+
+        match eval_int_binary(operator, left, right)? {
+            ArithOutcome::Value(value) => Some(ConstValue::Int(value)),
+            ArithOutcome::Overflow => {
+                diagnostics.push(diagnostic_at(
+                    db,
+                    key,
+                    "constant expression overflows".to_string(),
+                ));
+                None
+            }
+            ArithOutcome::DivByZero => {
+                diagnostics.push(diagnostic_at(db, key, "constant division by zero".to_string()));
+                None
+            }
+        }
+    }
+
+    fn checked_int(
+        result: Option<i64>,
+        db: &impl ConstEvalDatabase,
+        key: (hir::FnBody, hir::Expression),
+        diagnostics: &mut Vec<Diagnostic>,
+    ) -> Option<ConstValue> {
+        match result {
+            Some(value) => Some(ConstValue::Int(value)),
+            None => {
+                diagnostics.push(diagnostic_at(
+                    db,
+                    key,
+                    "constant expression overflows".to_string(),
+                ));
+                None
+            }
+        }
+    }
+
+    fn diagnostic_at(
+        db: &impl ConstEvalDatabase,
+        (fn_body_id, expression): (hir::FnBody, hir::Expression),
+        message: String,
+    ) -> Diagnostic {
+        let span = db.fn_body(fn_body_id).span(expression);
+        Diagnostic::new(message, span)
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn add_sub_mul_overflow() {
+            assert_eq!(
+                eval_int_binary(hir::BinaryOperator::Add, std::i64::MAX, 1),
+                Some(ArithOutcome::Overflow),
+            );
+            assert_eq!(
+                eval_int_binary(hir::BinaryOperator::Sub, std::i64::MIN, 1),
+                Some(ArithOutcome::Overflow),
+            );
+            assert_eq!(
+                eval_int_binary(hir::BinaryOperator::Mul, std::i64::MAX, 2),
+                Some(ArithOutcome::Overflow),
+            );
+        }
+
+        #[test]
+        fn div_by_zero_is_not_reported_as_overflow() {
+            assert_eq!(
+                eval_int_binary(hir::BinaryOperator::Div, 1, 0),
+                Some(ArithOutcome::DivByZero),
+            );
+        }
+
+        #[test]
+        fn div_overflow_is_still_overflow() {
+            // The one integer division that can overflow: MIN / -1.
+            assert_eq!(
+                eval_int_binary(hir::BinaryOperator::Div, std::i64::MIN, -1),
+                Some(ArithOutcome::Overflow),
+            );
+        }
+
+        #[test]
+        fn non_overflowing_arithmetic_folds_to_a_value() {
+            assert_eq!(
+                eval_int_binary(hir::BinaryOperator::Add, 2, 3),
+                Some(ArithOutcome::Value(5)),
+            );
+            assert_eq!(
+                eval_int_binary(hir::BinaryOperator::Div, 7, 2),
+                Some(ArithOutcome::Value(3)),
+            );
+        }
+    }
+}
@@ -0,0 +1,35 @@
+use crate::FileName;
+use lark_debug_derive::DebugWith;
+
+/// Pairs a value with the `FileName` it originated from. Many of our
+/// values (in particular, `Span<CurrentFile>`) are only meaningful
+/// relative to *some* file, but don't carry that file around with them
+/// (so that e.g. two spans from different files can still be compared
+/// structurally). `InFile` is how we recover that context when it's
+/// needed again, e.g. to resolve a span back to line/column
+/// information or to render a diagnostic.
+#[derive(Copy, Clone, Debug, DebugWith, PartialEq, Eq, Hash)]
+pub struct InFile<T> {
+    pub file: FileName,
+    pub value: T,
+}
+
+impl<T> InFile<T> {
+    pub fn new(file: FileName, value: T) -> Self {
+        InFile { file, value }
+    }
+
+    pub fn as_ref(&self) -> InFile<&T> {
+        InFile {
+            file: self.file,
+            value: &self.value,
+        }
+    }
+
+    pub fn map<U>(self, f: impl FnOnce(T) -> U) -> InFile<U> {
+        InFile {
+            file: self.file,
+            value: f(self.value),
+        }
+    }
+}
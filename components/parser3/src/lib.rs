@@ -14,11 +14,18 @@ use lark_string::text::Text;
 use std::sync::Arc;
 
 pub mod current_file;
+pub mod in_file;
 mod lexer;
+pub mod line_col;
 pub mod span;
+use self::in_file::InFile;
+use self::line_col::LineCol;
 use self::span::CurrentFile;
 use self::span::Span;
 
+/// A `Span<CurrentFile>`, together with the `FileName` it is relative to.
+pub type FileSpan = InFile<Span<CurrentFile>>;
+
 salsa::query_group! {
     pub trait ParserDatabase: AsRef<GlobalIdentifierTables> + salsa::Database {
         fn file_names() -> Arc<Vec<FileName>> {
@@ -30,6 +37,14 @@ salsa::query_group! {
             type FileTextQuery;
             storage input;
         }
+
+        /// Resolves a byte offset within `file` to the (line, column)
+        /// it falls on, so tools can render a real underline instead
+        /// of just a byte range.
+        fn line_col(file: FileName, offset: u32) -> LineCol {
+            type LineColQuery;
+            use fn line_col::line_col;
+        }
     }
 }
 
@@ -38,7 +53,12 @@ pub struct FileName {
     pub id: GlobalIdentifier,
 }
 
-fn diagnostic(message: String, span: Span<CurrentFile>) -> Diagnostic {
-    drop(span); // FIXME -- Diagostic uses the old codemap spans
-    Diagnostic::new(message, parser::pos::Span::Synthetic)
+/// `Diagnostic` itself has no room for a `FileName` (its span is the
+/// old codemap-based `parser::pos::Span`), so we keep the `FileName`
+/// alongside it in an `InFile` instead of dropping it -- callers that
+/// need to render the diagnostic can pull `.file` back out and feed
+/// it, together with the span, to the `line_col` query.
+fn diagnostic(message: String, file_span: FileSpan) -> InFile<Diagnostic> {
+    let diagnostic = Diagnostic::new(message, file_span.value.into());
+    InFile::new(file_span.file, diagnostic)
 }
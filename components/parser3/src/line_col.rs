@@ -0,0 +1,34 @@
+use crate::FileName;
+use crate::ParserDatabase;
+use lark_debug_derive::DebugWith;
+
+/// A 0-indexed line and column, as you'd show a human in an editor
+/// (after adding 1 to each). Computed from a raw byte offset by
+/// scanning the file's text for preceding newlines.
+#[derive(Copy, Clone, Debug, DebugWith, PartialEq, Eq, Hash)]
+pub struct LineCol {
+    pub line: u64,
+    pub column: u64,
+}
+
+crate fn line_col(db: &impl ParserDatabase, file: FileName, offset: u32) -> LineCol {
+    let text = db.file_text(file);
+    let offset = offset as usize;
+
+    let mut line = 0;
+    let mut line_start = 0;
+    for (index, ch) in text.char_indices() {
+        if index >= offset {
+            break;
+        }
+
+        if ch == '\n' {
+            line += 1;
+            line_start = index + 1;
+        }
+    }
+
+    let column = text[line_start..offset.min(text.len())].chars().count() as u64;
+
+    LineCol { line, column }
+}